@@ -15,6 +15,12 @@ pub struct AppConfig {
     pub keycloak_realm: String,
     pub keycloak_client_id: String,
     pub keycloak_client_secret: Option<String>,
+
+    // Local auth settings
+    pub jwt_secret: String,
+
+    // How often to refresh the JWKS cache in the background, in seconds
+    pub jwks_refresh_secs: u64,
 }
 
 impl AppConfig {
@@ -33,6 +39,18 @@ impl AppConfig {
             keycloak_client_id: std::env::var("KEYCLOAK_CLIENT_ID")
                 .context("KEYCLOAK_CLIENT_ID must be set")?,
             keycloak_client_secret: std::env::var("KEYCLOAK_CLIENT_SECRET").ok(),
+            jwt_secret: std::env::var("JWT_SECRET").context("JWT_SECRET must be set")?,
+            jwks_refresh_secs: {
+                let secs: u64 = std::env::var("JWKS_REFRESH_SECS")
+                    .unwrap_or_else(|_| "300".to_string())
+                    .parse()
+                    .context("Invalid JWKS_REFRESH_SECS")?;
+                anyhow::ensure!(
+                    secs > 0,
+                    "JWKS_REFRESH_SECS must be greater than 0 (a zero interval would panic the background refresher)"
+                );
+                secs
+            },
         })
     }
 
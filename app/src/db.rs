@@ -1,7 +1,12 @@
 use anyhow::Result;
-use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
+use sqlx::{postgres::PgPoolOptions, migrate::Migrator, Pool, Postgres};
 use std::env;
 
+/// Ordered, versioned migrations embedded from `migrations/`, tracked in the
+/// `_sqlx_migrations` table so schema changes are reproducible instead of
+/// idempotent `CREATE TABLE IF NOT EXISTS` DDL.
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
 /// Establishes a connection pool to PostgreSQL database
 pub async fn create_pool() -> Result<Pool<Postgres>> {
     let database_url = env::var("DATABASE_URL")
@@ -53,87 +58,69 @@ pub async fn create_pool() -> Result<Pool<Postgres>> {
     Ok(pool)
 }
 
-/// Runs database migrations to create tables if they don't exist
+/// Applies any pending migrations from `migrations/` in a transaction per
+/// file, recording each applied version in `_sqlx_migrations`.
 pub async fn run_migrations(pool: &Pool<Postgres>) -> Result<()> {
-    log::info!("🔄 Starting database migrations...");
-
-    // Create users table
-    log::info!("📋 Creating users table...");
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS users (
-            id UUID PRIMARY KEY,
-            email VARCHAR(255) NOT NULL UNIQUE,
-            password_hash VARCHAR(255) NOT NULL,
-            role VARCHAR(50) NOT NULL DEFAULT 'user',
-            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
-            updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
-        );
-        "#,
-    )
-    .execute(pool)
-    .await
-    .map_err(|e| {
-        log::error!("❌ Failed to create users table: {}", e);
-        anyhow::anyhow!("Failed to create users table: {}", e)
-    })?;
-    log::info!("✅ Users table created successfully");
-
-    // Create tasks table
-    log::info!("📋 Creating tasks table...");
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS tasks (
-            id UUID PRIMARY KEY,
-            user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
-            title VARCHAR(255) NOT NULL,
-            description TEXT,
-            completed BOOLEAN NOT NULL DEFAULT FALSE,
-            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
-            updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
-        );
-        "#,
-    )
-    .execute(pool)
-    .await
-    .map_err(|e| {
-        log::error!("❌ Failed to create tasks table: {}", e);
-        anyhow::anyhow!("Failed to create tasks table: {}", e)
+    log::info!("🔄 Applying pending database migrations...");
+
+    MIGRATOR.run(pool).await.map_err(|e| {
+        log::error!("❌ Failed to apply migrations: {}", e);
+        anyhow::anyhow!("Failed to apply migrations: {}", e)
     })?;
-    log::info!("✅ Tasks table created successfully");
-
-    // Create indexes for better performance
-    log::info!("📊 Creating database indexes...");
-
-    let indexes = vec![
-        (
-            "idx_tasks_user_id",
-            "CREATE INDEX IF NOT EXISTS idx_tasks_user_id ON tasks(user_id);",
-        ),
-        (
-            "idx_tasks_completed",
-            "CREATE INDEX IF NOT EXISTS idx_tasks_completed ON tasks(completed);",
-        ),
-        (
-            "idx_users_email",
-            "CREATE INDEX IF NOT EXISTS idx_users_email ON users(email);",
-        ),
-        (
-            "idx_tasks_created_at",
-            "CREATE INDEX IF NOT EXISTS idx_tasks_created_at ON tasks(created_at);",
-        ),
-    ];
-
-    for (name, query) in indexes {
-        sqlx::query(query).execute(pool).await.map_err(|e| {
-            log::error!("❌ Failed to create index {}: {}", name, e);
-            anyhow::anyhow!("Failed to create index {}: {}", name, e)
+
+    log::info!("🎉 Database migrations up to date");
+    Ok(())
+}
+
+/// Rolls back the most recently applied migration by running its
+/// `.down.sql` file.
+pub async fn rollback_last_migration(pool: &Pool<Postgres>) -> Result<()> {
+    let applied = MIGRATOR.iter().filter(|m| m.migration_type.is_up_migration());
+
+    let mut applied_versions: Vec<i64> = Vec::new();
+    for migration in applied {
+        let row: Option<(bool,)> = sqlx::query_as(
+            "SELECT success FROM _sqlx_migrations WHERE version = $1",
+        )
+        .bind(migration.version)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| {
+            log::error!("❌ Failed to read migration history: {}", e);
+            anyhow::anyhow!("Failed to read migration history: {}", e)
         })?;
-        log::debug!("✅ Index {} created successfully", name);
+
+        if row.is_some() {
+            applied_versions.push(migration.version);
+        }
     }
 
-    log::info!("✅ All database indexes created successfully");
-    log::info!("🎉 Database migrations completed successfully");
+    let Some(&last_version) = applied_versions.last() else {
+        log::info!("ℹ️  No applied migrations to roll back");
+        return Ok(());
+    };
+
+    let target_version = applied_versions
+        .iter()
+        .rev()
+        .nth(1)
+        .copied()
+        .unwrap_or(0);
+
+    log::info!(
+        "⏪ Rolling back migration {} (to version {})",
+        last_version, target_version
+    );
+
+    MIGRATOR
+        .undo(pool, target_version)
+        .await
+        .map_err(|e| {
+            log::error!("❌ Failed to roll back migration {}: {}", last_version, e);
+            anyhow::anyhow!("Failed to roll back migration {}: {}", last_version, e)
+        })?;
+
+    log::info!("✅ Rolled back migration {}", last_version);
     Ok(())
 }
 
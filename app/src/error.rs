@@ -1,18 +1,82 @@
+use serde::Serialize;
 use thiserror::Error;
+use warp::http::StatusCode;
+use warp::{Rejection, Reply};
 
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Database error: {0}")]
     DatabaseError(String),
-    
+
     #[error("Unauthorized")]
     Unauthorized,
-    
+
     #[error("Not found")]
     NotFound,
-    
+
+    #[error("{0} already exists")]
+    EmailExists(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Validation error: {0}")]
+    Validation(String),
+
     #[error("Internal server error")]
     InternalServerError,
 }
 
-impl warp::reject::Reject for AppError {}
\ No newline at end of file
+impl warp::reject::Reject for AppError {}
+
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        if let sqlx::Error::Database(ref db_err) = err {
+            if db_err.is_unique_violation() {
+                if db_err.table() == Some("users") && db_err.constraint() == Some("users_email_key")
+                {
+                    return AppError::EmailExists("email".to_string());
+                }
+
+                return AppError::Conflict(db_err.message().to_string());
+            }
+        }
+
+        AppError::DatabaseError(err.to_string())
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// Renders a rejected `AppError` (or an unhandled warp rejection) as a JSON
+/// body with the matching HTTP status code.
+pub async fn handle_rejection(err: Rejection) -> Result<impl Reply, Rejection> {
+    let (status, message) = if let Some(app_err) = err.find::<AppError>() {
+        let status = match app_err {
+            AppError::EmailExists(_) | AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::Unauthorized => StatusCode::UNAUTHORIZED,
+            AppError::NotFound => StatusCode::NOT_FOUND,
+            AppError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::DatabaseError(_) | AppError::InternalServerError => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+        (status, app_err.to_string())
+    } else if err.is_not_found() {
+        (StatusCode::NOT_FOUND, "Not found".to_string())
+    } else {
+        log::error!("❌ Unhandled rejection: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal server error".to_string(),
+        )
+    };
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&ErrorResponse { error: message }),
+        status,
+    ))
+}
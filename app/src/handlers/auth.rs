@@ -0,0 +1,163 @@
+use crate::config::AppConfig;
+use crate::jwt;
+use crate::models::user::User;
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+use warp::Filter;
+
+#[derive(Deserialize)]
+pub struct RegisterRequest {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Deserialize)]
+pub struct LoginRequest {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Serialize)]
+pub struct RegisterResponse {
+    pub id: Uuid,
+    pub email: String,
+}
+
+#[derive(Serialize)]
+pub struct LoginResponse {
+    pub token: String,
+}
+
+const MIN_PASSWORD_LEN: usize = 8;
+
+/// Rejects obviously-bad registration input before it reaches Argon2/SQL.
+fn validate_credentials(email: &str, password: &str) -> Result<(), warp::Rejection> {
+    if email.trim().is_empty() || !email.contains('@') {
+        return Err(warp::reject::custom(crate::error::AppError::Validation(
+            "A valid email address is required".to_string(),
+        )));
+    }
+
+    if password.len() < MIN_PASSWORD_LEN {
+        return Err(warp::reject::custom(crate::error::AppError::Validation(
+            format!("Password must be at least {} characters", MIN_PASSWORD_LEN),
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn routes(
+    config: &AppConfig,
+    pool: PgPool,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let config = config.clone();
+
+    // POST /api/auth/register - Create a local account
+    let register = {
+        let pool = pool.clone();
+        warp::path("api")
+            .and(warp::path("auth"))
+            .and(warp::path("register"))
+            .and(warp::path::end())
+            .and(warp::post())
+            .and(warp::body::json())
+            .and_then(move |req: RegisterRequest| register(req, pool.clone()))
+    };
+
+    // POST /api/auth/login - Exchange email/password for a session JWT
+    let login = {
+        let pool = pool.clone();
+        let config = config.clone();
+        warp::path("api")
+            .and(warp::path("auth"))
+            .and(warp::path("login"))
+            .and(warp::path::end())
+            .and(warp::post())
+            .and(warp::body::json())
+            .and_then(move |req: LoginRequest| login(req, pool.clone(), config.clone()))
+    };
+
+    register.or(login)
+}
+
+async fn register(req: RegisterRequest, pool: PgPool) -> Result<impl warp::Reply, warp::Rejection> {
+    log::info!("📝 Registering new user: {}", req.email);
+
+    validate_credentials(&req.email, &req.password)?;
+
+    let salt = SaltString::generate(&mut OsRng);
+    let password_hash = Argon2::default()
+        .hash_password(req.password.as_bytes(), &salt)
+        .map_err(|e| {
+            log::error!("❌ Failed to hash password: {}", e);
+            warp::reject::custom(crate::error::AppError::InternalServerError)
+        })?
+        .to_string();
+
+    let user_id = Uuid::new_v4();
+
+    let user = sqlx::query_as::<_, User>(
+        "INSERT INTO users (id, email, password_hash, role)
+         VALUES ($1, $2, $3, 'user')
+         RETURNING id, email, password_hash, role, created_at, updated_at",
+    )
+    .bind(&user_id)
+    .bind(&req.email)
+    .bind(&password_hash)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| {
+        log::error!("❌ Failed to create user in database: {}", e);
+        warp::reject::custom(crate::error::AppError::from(e))
+    })?;
+
+    log::info!("✅ Successfully registered user {}", user.id);
+
+    Ok(warp::reply::json(&RegisterResponse {
+        id: user.id,
+        email: user.email,
+    }))
+}
+
+async fn login(
+    req: LoginRequest,
+    pool: PgPool,
+    config: AppConfig,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    log::info!("🔑 Logging in user: {}", req.email);
+
+    let user = sqlx::query_as::<_, User>(
+        "SELECT id, email, password_hash, role, created_at, updated_at FROM users WHERE email = $1",
+    )
+    .bind(&req.email)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| {
+        log::error!("❌ Failed to look up user: {}", e);
+        warp::reject::custom(crate::error::AppError::from(e))
+    })?
+    .ok_or_else(|| warp::reject::custom(crate::error::AppError::Unauthorized))?;
+
+    let parsed_hash = PasswordHash::new(&user.password_hash)
+        .map_err(|_| warp::reject::custom(crate::error::AppError::InternalServerError))?;
+
+    if Argon2::default()
+        .verify_password(req.password.as_bytes(), &parsed_hash)
+        .is_err()
+    {
+        return Err(warp::reject::custom(crate::error::AppError::Unauthorized));
+    }
+
+    let token = jwt::sign_session_jwt(&user.id.to_string(), &user.email, &config).map_err(|e| {
+        log::error!("❌ Failed to mint session token: {}", e);
+        warp::reject::custom(crate::error::AppError::InternalServerError)
+    })?;
+
+    log::info!("✅ Successfully logged in user {}", user.id);
+
+    Ok(warp::reply::json(&LoginResponse { token }))
+}
@@ -2,6 +2,7 @@ use crate::config::AppConfig;
 use sqlx::PgPool;
 use warp::Filter;
 
+pub mod auth;
 pub mod task;
 
 pub fn task_routes(
@@ -10,3 +11,10 @@ pub fn task_routes(
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     task::routes(config, pool)
 }
+
+pub fn auth_routes(
+    config: &AppConfig,
+    pool: PgPool,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    auth::routes(config, pool)
+}
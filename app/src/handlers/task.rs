@@ -1,10 +1,22 @@
 use crate::config::AppConfig;
-use crate::models::task::Task;
+use crate::error::AppError;
+use crate::middleware::auth::{auth_middleware, AuthUser};
+use crate::models::task::{Task, TaskUpdate};
+use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::collections::HashSet;
+use std::sync::RwLock;
 use uuid::Uuid;
 use warp::Filter;
 
+// Process-local cache of `users.id`s we've already seen, so `resolve_user_id`
+// only writes to `users` the first time a principal shows up instead of on
+// every task request.
+lazy_static! {
+    static ref KNOWN_USER_IDS: RwLock<HashSet<Uuid>> = RwLock::new(HashSet::new());
+}
+
 #[derive(Deserialize)]
 pub struct CreateTaskRequest {
     pub title: String,
@@ -22,31 +34,92 @@ pub struct TaskResponse {
     pub updated_at: String,
 }
 
+impl From<Task> for TaskResponse {
+    fn from(task: Task) -> Self {
+        TaskResponse {
+            id: task.id,
+            user_id: task.user_id,
+            title: task.title,
+            description: task.description,
+            completed: task.completed,
+            created_at: task.created_at.to_rfc3339(),
+            updated_at: task.updated_at.to_rfc3339(),
+        }
+    }
+}
+
 pub fn routes(
     config: &AppConfig,
     pool: PgPool,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
-    let _config = config.clone();
+    let auth = auth_middleware(config.clone());
 
-    // GET /api/tasks - List all tasks
+    // GET /api/tasks - List the caller's tasks
     let get_tasks = {
         let pool = pool.clone();
         warp::path("api")
             .and(warp::path("tasks"))
             .and(warp::path::end())
             .and(warp::get())
-            .and_then(move || list_tasks(pool.clone()))
+            .and(auth.clone())
+            .and_then(move |auth_user: AuthUser| list_tasks(pool.clone(), auth_user))
     };
 
-    // POST /api/tasks - Create a new task
+    // POST /api/tasks - Create a new task owned by the caller
     let create_tasks = {
         let pool = pool.clone();
         warp::path("api")
             .and(warp::path("tasks"))
             .and(warp::path::end())
             .and(warp::post())
+            .and(auth.clone())
+            .and(warp::body::json())
+            .and_then(move |auth_user: AuthUser, req: CreateTaskRequest| {
+                create_task(req, pool.clone(), auth_user)
+            })
+    };
+
+    // GET /api/tasks/{id} - Fetch one of the caller's tasks
+    let get_task = {
+        let pool = pool.clone();
+        warp::path("api")
+            .and(warp::path("tasks"))
+            .and(warp::path::param())
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(auth.clone())
+            .and_then(move |task_id: Uuid, auth_user: AuthUser| {
+                get_task(task_id, pool.clone(), auth_user)
+            })
+    };
+
+    // PUT /api/tasks/{id} - Partially update one of the caller's tasks
+    let update_task = {
+        let pool = pool.clone();
+        warp::path("api")
+            .and(warp::path("tasks"))
+            .and(warp::path::param())
+            .and(warp::path::end())
+            .and(warp::put())
+            .and(auth.clone())
             .and(warp::body::json())
-            .and_then(move |req: CreateTaskRequest| create_task(req, pool.clone()))
+            .and_then(move |task_id: Uuid, auth_user: AuthUser, req: TaskUpdate| {
+                update_task(task_id, req, pool.clone(), auth_user)
+            })
+    };
+
+    // DELETE /api/tasks/{id} - Delete one of the caller's tasks
+    let delete_task = {
+        let pool = pool.clone();
+        warp::path("api")
+            .and(warp::path("tasks"))
+            .and(warp::path::param())
+            .and(warp::path::end())
+            .and(warp::delete())
+            .and(auth.clone())
+            .and_then(move |task_id: Uuid, auth_user: AuthUser| {
+                delete_task(task_id, pool.clone(), auth_user)
+            })
     };
 
     // Health check endpoint
@@ -55,22 +128,63 @@ pub fn routes(
         .and(warp::get())
         .map(|| warp::reply::json(&serde_json::json!({"status": "ok"})));
 
-    get_tasks.or(create_tasks).or(health)
+    get_tasks
+        .or(create_tasks)
+        .or(get_task)
+        .or(update_task)
+        .or(delete_task)
+        .or(health)
+}
+
+/// Resolves an `AuthUser` (from either a Keycloak or local session JWT) to a
+/// `users.id`, creating the user row on first sight so tasks always have a
+/// valid owner to scope against.
+///
+/// The synthesized email is derived from `user_id`, never from the unverified
+/// `preferred_username` claim, since that claim isn't unique and colliding
+/// with another user's real email would 409 every request for both accounts.
+async fn resolve_user_id(pool: &PgPool, auth_user: &AuthUser) -> Result<Uuid, AppError> {
+    let user_id = Uuid::parse_str(&auth_user.sub)
+        .unwrap_or_else(|_| Uuid::new_v5(&Uuid::NAMESPACE_OID, auth_user.sub.as_bytes()));
+
+    if KNOWN_USER_IDS.read().unwrap().contains(&user_id) {
+        return Ok(user_id);
+    }
+
+    sqlx::query(
+        "INSERT INTO users (id, email, password_hash, role)
+         VALUES ($1, $2, '', 'user')
+         ON CONFLICT (id) DO NOTHING",
+    )
+    .bind(user_id)
+    .bind(format!("{user_id}@keycloak.local"))
+    .execute(pool)
+    .await?;
+
+    KNOWN_USER_IDS.write().unwrap().insert(user_id);
+
+    Ok(user_id)
 }
 
-async fn list_tasks(pool: PgPool) -> Result<impl warp::Reply, warp::Rejection> {
-    log::info!("📋 Fetching all tasks from database...");
+async fn list_tasks(pool: PgPool, auth_user: AuthUser) -> Result<impl warp::Reply, warp::Rejection> {
+    log::info!("📋 Fetching tasks for user {}...", auth_user.sub);
+
+    let user_id = resolve_user_id(&pool, &auth_user)
+        .await
+        .map_err(warp::reject::custom)?;
 
     let tasks = sqlx::query_as::<_, Task>(
-        "SELECT id, user_id, title, description, completed, created_at, updated_at 
-         FROM tasks 
+        "SELECT id, user_id, title, description, completed, created_at, updated_at
+         FROM tasks
+         WHERE user_id = $1
          ORDER BY created_at DESC",
     )
+    .bind(user_id)
     .fetch_all(&pool)
     .await
     .map_err(|e| {
         log::error!("❌ Failed to fetch tasks from database: {}", e);
-        warp::reject::custom(crate::error::AppError::DatabaseError(e.to_string()))
+        warp::reject::custom(AppError::from(e))
     })?;
 
     log::info!(
@@ -78,18 +192,7 @@ async fn list_tasks(pool: PgPool) -> Result<impl warp::Reply, warp::Rejection> {
         tasks.len()
     );
 
-    let task_responses: Vec<TaskResponse> = tasks
-        .into_iter()
-        .map(|task| TaskResponse {
-            id: task.id,
-            user_id: task.user_id,
-            title: task.title,
-            description: task.description,
-            completed: task.completed,
-            created_at: task.created_at.to_rfc3339(),
-            updated_at: task.updated_at.to_rfc3339(),
-        })
-        .collect();
+    let task_responses: Vec<TaskResponse> = tasks.into_iter().map(TaskResponse::from).collect();
 
     Ok(warp::reply::json(&task_responses))
 }
@@ -97,20 +200,22 @@ async fn list_tasks(pool: PgPool) -> Result<impl warp::Reply, warp::Rejection> {
 async fn create_task(
     req: CreateTaskRequest,
     pool: PgPool,
+    auth_user: AuthUser,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     log::info!("📝 Creating new task: {}", req.title);
 
-    // For now, use a dummy user_id. In a real app, this would come from the authenticated user
-    let dummy_user_id = Uuid::new_v4();
+    let user_id = resolve_user_id(&pool, &auth_user)
+        .await
+        .map_err(warp::reject::custom)?;
     let task_id = Uuid::new_v4();
 
     let inserted_task = sqlx::query_as::<_, Task>(
-        "INSERT INTO tasks (id, user_id, title, description, completed) 
-         VALUES ($1, $2, $3, $4, $5) 
+        "INSERT INTO tasks (id, user_id, title, description, completed)
+         VALUES ($1, $2, $3, $4, $5)
          RETURNING id, user_id, title, description, completed, created_at, updated_at",
     )
     .bind(&task_id)
-    .bind(&dummy_user_id)
+    .bind(&user_id)
     .bind(&req.title)
     .bind(&req.description)
     .bind(false)
@@ -118,7 +223,7 @@ async fn create_task(
     .await
     .map_err(|e| {
         log::error!("❌ Failed to create task in database: {}", e);
-        warp::reject::custom(crate::error::AppError::DatabaseError(e.to_string()))
+        warp::reject::custom(AppError::from(e))
     })?;
 
     log::info!(
@@ -126,15 +231,106 @@ async fn create_task(
         inserted_task.id
     );
 
-    let task_response = TaskResponse {
-        id: inserted_task.id,
-        user_id: inserted_task.user_id,
-        title: inserted_task.title,
-        description: inserted_task.description,
-        completed: inserted_task.completed,
-        created_at: inserted_task.created_at.to_rfc3339(),
-        updated_at: inserted_task.updated_at.to_rfc3339(),
-    };
+    Ok(warp::reply::json(&TaskResponse::from(inserted_task)))
+}
+
+async fn get_task(
+    task_id: Uuid,
+    pool: PgPool,
+    auth_user: AuthUser,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    log::info!("🔍 Fetching task {}...", task_id);
+
+    let user_id = resolve_user_id(&pool, &auth_user)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let task = sqlx::query_as::<_, Task>(
+        "SELECT id, user_id, title, description, completed, created_at, updated_at
+         FROM tasks
+         WHERE id = $1 AND user_id = $2",
+    )
+    .bind(task_id)
+    .bind(user_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| {
+        log::error!("❌ Failed to fetch task from database: {}", e);
+        warp::reject::custom(AppError::from(e))
+    })?
+    .ok_or_else(|| warp::reject::custom(AppError::NotFound))?;
+
+    Ok(warp::reply::json(&TaskResponse::from(task)))
+}
+
+async fn update_task(
+    task_id: Uuid,
+    req: TaskUpdate,
+    pool: PgPool,
+    auth_user: AuthUser,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    log::info!("✏️  Updating task {}...", task_id);
+
+    let user_id = resolve_user_id(&pool, &auth_user)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let updated_task = sqlx::query_as::<_, Task>(
+        "UPDATE tasks
+         SET title = COALESCE($1, title),
+             description = COALESCE($2, description),
+             completed = COALESCE($3, completed),
+             updated_at = NOW()
+         WHERE id = $4 AND user_id = $5
+         RETURNING id, user_id, title, description, completed, created_at, updated_at",
+    )
+    .bind(&req.title)
+    .bind(&req.description)
+    .bind(&req.completed)
+    .bind(task_id)
+    .bind(user_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| {
+        log::error!("❌ Failed to update task in database: {}", e);
+        warp::reject::custom(AppError::from(e))
+    })?
+    .ok_or_else(|| warp::reject::custom(AppError::NotFound))?;
+
+    log::info!("✅ Successfully updated task {}", updated_task.id);
+
+    Ok(warp::reply::json(&TaskResponse::from(updated_task)))
+}
+
+async fn delete_task(
+    task_id: Uuid,
+    pool: PgPool,
+    auth_user: AuthUser,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    log::info!("🗑️  Deleting task {}...", task_id);
+
+    let user_id = resolve_user_id(&pool, &auth_user)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let result = sqlx::query("DELETE FROM tasks WHERE id = $1 AND user_id = $2")
+        .bind(task_id)
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| {
+            log::error!("❌ Failed to delete task from database: {}", e);
+            warp::reject::custom(AppError::from(e))
+        })?;
+
+    if result.rows_affected() == 0 {
+        return Err(warp::reject::custom(AppError::NotFound));
+    }
+
+    log::info!("✅ Successfully deleted task {}", task_id);
 
-    Ok(warp::reply::json(&task_response))
+    Ok(warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({"status": "deleted"})),
+        warp::http::StatusCode::OK,
+    ))
 }
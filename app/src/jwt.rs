@@ -1,11 +1,25 @@
 use anyhow::Result;
+use hmac::{Hmac, Mac};
 use lazy_static::lazy_static;
+use rsa::pkcs1v15::{Signature, VerifyingKey};
+use rsa::signature::Verifier;
+use rsa::{BigUint, RsaPublicKey};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sha2::Sha256;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
 use crate::config::AppConfig;
 
+// Lifetime of a locally-minted session JWT.
+const SESSION_TOKEN_TTL_SECS: u64 = 24 * 60 * 60;
+
+// Minimum time between on-demand JWKS refreshes triggered by an unknown kid,
+// so a flood of bogus tokens can't hammer Keycloak.
+const ON_DEMAND_REFRESH_COOLDOWN_SECS: u64 = 10;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub sub: String,
@@ -24,6 +38,37 @@ lazy_static! {
         std::sync::RwLock::new(HashMap::new());
 }
 
+// Unix timestamp of the last on-demand (unknown-kid-triggered) refresh.
+static LAST_ON_DEMAND_REFRESH: AtomicU64 = AtomicU64::new(0);
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Spawns a background task that re-populates the JWKS cache on a fixed
+/// interval, so a Keycloak key rotation doesn't fail every token until the
+/// process restarts.
+pub fn spawn_jwks_refresher(cfg: AppConfig) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(cfg.jwks_refresh_secs));
+        interval.tick().await; // first tick fires immediately; main already tried to populate the cache at startup
+
+        loop {
+            interval.tick().await;
+
+            let cfg = cfg.clone();
+            match tokio::task::spawn_blocking(move || populate_jwks_cache(&cfg)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => log::warn!("⚠️  Periodic JWKS refresh failed: {}", e),
+                Err(e) => log::warn!("⚠️  Periodic JWKS refresh task panicked: {}", e),
+            }
+        }
+    });
+}
+
 // Fetches the JWKS from Keycloak
 fn fetch_jwks(cfg: &AppConfig) -> Result<Value> {
     let response = ureq::get(&cfg.jwks_url()).call()?.into_json::<Value>()?;
@@ -101,12 +146,202 @@ fn base64url_decode(input: &str) -> Result<Vec<u8>> {
         
         i += 4;
     }
-    
+
     Ok(result)
 }
 
-pub fn verify_keycloak_jwt(token: &str, cfg: &AppConfig) -> Result<Claims> {
-    // Parse the JWT header to get the kid
+// Simple base64url encode (no padding) without external dependencies, to match
+// base64url_decode above.
+fn base64url_encode(input: &[u8]) -> String {
+    let chars = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut result = String::new();
+
+    for chunk in input.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+        let val = (b0 << 16) | (b1 << 8) | b2;
+
+        result.push(chars[((val >> 18) & 0x3F) as usize] as char);
+        result.push(chars[((val >> 12) & 0x3F) as usize] as char);
+        if chunk.len() > 1 {
+            result.push(chars[((val >> 6) & 0x3F) as usize] as char);
+        }
+        if chunk.len() > 2 {
+            result.push(chars[(val & 0x3F) as usize] as char);
+        }
+    }
+
+    result.replace('+', "-").replace('/', "_")
+}
+
+// Mints a session JWT (HS256, signed with the app's JWT_SECRET) for a locally
+// authenticated user, so the rest of the app can treat it the same as a
+// Keycloak-issued token.
+pub fn sign_session_jwt(sub: &str, preferred_username: &str, cfg: &AppConfig) -> Result<String> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let header = serde_json::json!({ "alg": "HS256", "typ": "JWT" });
+    let payload = serde_json::json!({
+        "sub": sub,
+        "preferred_username": preferred_username,
+        "iss": "local",
+        "aud": "local",
+        "azp": "local",
+        "scope": "",
+        "iat": now,
+        "exp": now + SESSION_TOKEN_TTL_SECS,
+    });
+
+    let signing_input = format!(
+        "{}.{}",
+        base64url_encode(&serde_json::to_vec(&header)?),
+        base64url_encode(&serde_json::to_vec(&payload)?)
+    );
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(cfg.jwt_secret.as_bytes())
+        .map_err(|e| anyhow::anyhow!("Invalid JWT secret: {}", e))?;
+    mac.update(signing_input.as_bytes());
+    let signature = base64url_encode(&mac.finalize().into_bytes());
+
+    Ok(format!("{}.{}", signing_input, signature))
+}
+
+// Verifies a locally-minted session JWT and returns its claims. Kept separate
+// from verify_keycloak_jwt since it checks an HMAC against JWT_SECRET rather
+// than an RSA signature against the JWKS cache.
+pub fn verify_session_jwt(token: &str, cfg: &AppConfig) -> Result<Claims> {
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() != 3 {
+        return Err(anyhow::anyhow!("Invalid JWT format"));
+    }
+
+    let header: Value = serde_json::from_slice(&base64url_decode(parts[0])?)?;
+    let alg = header
+        .get("alg")
+        .and_then(|a| a.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing alg in JWT header"))?;
+    if alg != "HS256" {
+        return Err(anyhow::anyhow!("Unsupported JWT algorithm: {}", alg));
+    }
+
+    let signing_input = format!("{}.{}", parts[0], parts[1]);
+    let mut mac = Hmac::<Sha256>::new_from_slice(cfg.jwt_secret.as_bytes())
+        .map_err(|e| anyhow::anyhow!("Invalid JWT secret: {}", e))?;
+    mac.update(signing_input.as_bytes());
+    mac.verify_slice(&base64url_decode(parts[2])?)
+        .map_err(|_| anyhow::anyhow!("Invalid JWT signature"))?;
+
+    let payload: Value = serde_json::from_slice(&base64url_decode(parts[1])?)?;
+
+    let exp = payload
+        .get("exp")
+        .and_then(|e| e.as_u64())
+        .ok_or_else(|| anyhow::anyhow!("Missing exp in JWT payload"))?;
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    if exp < now {
+        return Err(anyhow::anyhow!("Token expired"));
+    }
+
+    Ok(Claims {
+        sub: payload
+            .get("sub")
+            .and_then(|s| s.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        preferred_username: payload
+            .get("preferred_username")
+            .and_then(|u| u.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        exp,
+        iat: payload.get("iat").and_then(|i| i.as_u64()).unwrap_or(0),
+        aud: payload
+            .get("aud")
+            .and_then(|a| a.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        iss: payload
+            .get("iss")
+            .and_then(|i| i.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        azp: payload
+            .get("azp")
+            .and_then(|a| a.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        scope: payload
+            .get("scope")
+            .and_then(|s| s.as_str())
+            .unwrap_or_default()
+            .to_string(),
+    })
+}
+
+// Reads the cached `n:e` pair for a `kid`, if present. Pure cache lookup, no
+// network I/O, so it's safe to call from sync code.
+fn get_cached_jwk(kid: &str) -> Option<String> {
+    JWKS_CACHE.read().unwrap().get(kid).cloned()
+}
+
+// Ensures the cache holds an entry for `kid`, re-fetching the JWKS once (off
+// the async runtime, via spawn_blocking) if it's missing so a rotated signing
+// key doesn't fail every token until restart. Cooldown-guarded so a flood of
+// tokens with a bogus kid can't hammer Keycloak.
+async fn ensure_jwk_cached(kid: &str, cfg: &AppConfig) -> Result<()> {
+    if get_cached_jwk(kid).is_some() {
+        return Ok(());
+    }
+
+    let now = now_secs();
+    let last_refresh = LAST_ON_DEMAND_REFRESH.load(Ordering::Relaxed);
+    if now.saturating_sub(last_refresh) < ON_DEMAND_REFRESH_COOLDOWN_SECS {
+        return Err(anyhow::anyhow!("Unknown kid: {} (refresh on cooldown)", kid));
+    }
+    LAST_ON_DEMAND_REFRESH.store(now, Ordering::Relaxed);
+
+    let cfg = cfg.clone();
+    tokio::task::spawn_blocking(move || populate_jwks_cache(&cfg))
+        .await
+        .map_err(|e| anyhow::anyhow!("JWKS refresh task panicked: {}", e))??;
+
+    Ok(())
+}
+
+// Verifies the RS256 signature of a JWT against the cached JWKS, returning an
+// error on an unknown kid or a signature that doesn't check out.
+fn verify_signature(parts: &[&str], kid: &str) -> Result<()> {
+    let key_data =
+        get_cached_jwk(kid).ok_or_else(|| anyhow::anyhow!("Unknown kid: {}", kid))?;
+    let (n_b64, e_b64) = key_data
+        .split_once(':')
+        .ok_or_else(|| anyhow::anyhow!("Malformed cached JWK for kid: {}", kid))?;
+
+    let n = BigUint::from_bytes_be(&base64url_decode(n_b64)?);
+    let e = BigUint::from_bytes_be(&base64url_decode(e_b64)?);
+    let public_key = RsaPublicKey::new(n, e)?;
+    let verifying_key = VerifyingKey::<Sha256>::new(public_key);
+
+    let signing_input = format!("{}.{}", parts[0], parts[1]);
+    let signature_bytes = base64url_decode(parts[2])?;
+    let signature = Signature::try_from(signature_bytes.as_slice())?;
+
+    verifying_key
+        .verify(signing_input.as_bytes(), &signature)
+        .map_err(|_| anyhow::anyhow!("Invalid JWT signature"))
+}
+
+pub async fn verify_keycloak_jwt(token: &str, cfg: &AppConfig) -> Result<Claims> {
+    // Parse the JWT header to get the alg and kid
     let parts: Vec<&str> = token.split('.').collect();
     if parts.len() != 3 {
         return Err(anyhow::anyhow!("Invalid JWT format"));
@@ -114,11 +349,27 @@ pub fn verify_keycloak_jwt(token: &str, cfg: &AppConfig) -> Result<Claims> {
 
     let header_bytes = base64url_decode(parts[0])?;
     let header: Value = serde_json::from_slice(&header_bytes)?;
-    let _kid = header
+
+    let alg = header
+        .get("alg")
+        .and_then(|a| a.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing alg in JWT header"))?;
+    if alg != "RS256" {
+        return Err(anyhow::anyhow!("Unsupported JWT algorithm: {}", alg));
+    }
+
+    let kid = header
         .get("kid")
         .and_then(|k| k.as_str())
         .ok_or_else(|| anyhow::anyhow!("Missing kid in JWT header"))?;
 
+    // Reject the token outright on any signature mismatch or unknown kid,
+    // before the claims inside the payload are trusted for anything. A kid
+    // absent from the cache triggers an off-thread JWKS refresh rather than blocking
+    // the async runtime on the Keycloak round-trip.
+    ensure_jwk_cached(kid, cfg).await?;
+    verify_signature(&parts, kid)?;
+
     // Parse the payload
     let payload_bytes = base64url_decode(parts[1])?;
     let payload: Value = serde_json::from_slice(&payload_bytes)?;
@@ -12,7 +12,7 @@ mod middleware;
 mod models;
 
 use config::AppConfig;
-use handlers::task_routes;
+use handlers::{auth_routes, task_routes};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -25,9 +25,24 @@ async fn main() -> Result<()> {
     // Load configuration
     let config = AppConfig::from_env()?;
 
+    // `migrate` / `migrate down` apply or roll back migrations and exit,
+    // without starting the server.
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("migrate") {
+        let pool = db::create_pool().await?;
+
+        if args.get(2).map(String::as_str) == Some("down") {
+            db::rollback_last_migration(&pool).await?;
+        } else {
+            db::run_migrations(&pool).await?;
+        }
+
+        return Ok(());
+    }
+
     // Initialize database connection and run migrations
     log::info!("🚀 Starting Rust API application...");
-    
+
     let pool = match db::create_pool().await {
         Ok(pool) => {
             log::info!("✅ Database connection established successfully");
@@ -58,13 +73,19 @@ async fn main() -> Result<()> {
         println!("   This is expected if Keycloak is not running yet.");
     }
 
+    // Keep the JWKS cache fresh across Keycloak key rotations
+    jwt::spawn_jwks_refresher(config.clone());
+
     // Create routes with database pool
-    let routes = task_routes(&config, pool).with(
-        warp::cors()
-            .allow_any_origin()
-            .allow_headers(vec!["content-type", "authorization"])
-            .allow_methods(vec!["GET", "POST", "PUT", "DELETE"]),
-    );
+    let routes = task_routes(&config, pool.clone())
+        .or(auth_routes(&config, pool))
+        .recover(error::handle_rejection)
+        .with(
+            warp::cors()
+                .allow_any_origin()
+                .allow_headers(vec!["content-type", "authorization"])
+                .allow_methods(vec!["GET", "POST", "PUT", "DELETE"]),
+        );
 
     // Start server
     let addr: SocketAddr = ([0, 0, 0, 0], config.app_port).into();
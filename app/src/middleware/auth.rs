@@ -1,6 +1,6 @@
 use crate::config::AppConfig;
 use crate::error::AppError;
-use crate::jwt::verify_keycloak_jwt;
+use crate::jwt::{verify_keycloak_jwt, verify_session_jwt};
 use warp::Filter;
 
 #[derive(Debug, Clone)]
@@ -13,9 +13,18 @@ pub struct AuthUser {
 pub fn auth_middleware(
     config: AppConfig,
 ) -> impl Filter<Extract = (AuthUser,), Error = warp::Rejection> + Clone {
-    warp::header::<String>("authorization").and_then(move |auth_header: String| {
+    warp::header::headers_cloned().and_then(move |headers: warp::http::HeaderMap| {
         let config = config.clone();
         async move {
+            // Missing or non-UTF8 headers land here as `None` rather than a
+            // warp-internal `MissingHeader`/`InvalidHeader` rejection, so the
+            // existing 401 path in `handle_rejection` is actually reached.
+            let auth_header = headers
+                .get("authorization")
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+                .ok_or_else(|| warp::reject::custom(AppError::Unauthorized))?;
+
             // Extract Bearer token
             if !auth_header.starts_with("Bearer ") {
                 return Err(warp::reject::custom(AppError::Unauthorized));
@@ -23,8 +32,14 @@ pub fn auth_middleware(
 
             let token = &auth_header[7..]; // Remove "Bearer " prefix
 
-            // Verify the JWT token
-            match verify_keycloak_jwt(token, &config) {
+            // Accept either a Keycloak-issued token or a locally-minted
+            // session token, so the app works with or without Keycloak.
+            let claims = match verify_keycloak_jwt(token, &config).await {
+                Ok(claims) => Ok(claims),
+                Err(_) => verify_session_jwt(token, &config),
+            };
+
+            match claims {
                 Ok(claims) => Ok(AuthUser {
                     sub: claims.sub,
                     preferred_username: claims.preferred_username,